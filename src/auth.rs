@@ -0,0 +1,79 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Pluggable token-validation strategy, checked by the [`require_auth`] middleware.
+///
+/// Keeping this behind a trait (rather than hardcoding a `HashSet` lookup) lets routes
+/// that need different auth semantics later reuse the same middleware shape.
+pub trait ApiAuth: Clone + Send + Sync + 'static {
+    fn is_valid(&self, token: &str) -> bool;
+}
+
+/// Validates bearer tokens against a fixed set loaded at startup.
+#[derive(Clone)]
+pub struct TokenAuth {
+    tokens: Arc<HashSet<String>>,
+}
+
+impl TokenAuth {
+    /// Builds the auth layer from the resolved token list (see [`crate::config::Config`]
+    /// for where those tokens come from). Returns `None` for an empty list, meaning
+    /// auth is disabled.
+    pub fn from_tokens(tokens: impl IntoIterator<Item = String>) -> Option<Self> {
+        let tokens: HashSet<String> = tokens.into_iter().collect();
+        if tokens.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            tokens: Arc::new(tokens),
+        })
+    }
+}
+
+impl ApiAuth for TokenAuth {
+    fn is_valid(&self, token: &str) -> bool {
+        self.tokens.contains(token)
+    }
+}
+
+fn extract_token(req: &Request) -> Option<String> {
+    if let Some(value) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+    {
+        if let Some(token) = value.strip_prefix("Bearer ") {
+            return Some(token.to_string());
+        }
+    }
+
+    req.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string)
+    })
+}
+
+/// Tower middleware that rejects requests without a token accepted by `A`.
+///
+/// Mount it via `middleware::from_fn_with_state` on the routes that take or
+/// serve dropped content (`/upload`, `/message`, `/files`, `/files/{name}`), so
+/// only the index/favicon stay public.
+pub async fn require_auth<A: ApiAuth>(
+    State(auth): State<A>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    match extract_token(&req) {
+        Some(token) if auth.is_valid(&token) => Ok(next.run(req).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
@@ -0,0 +1,144 @@
+use std::{env, fs, path::PathBuf};
+
+use serde::Deserialize;
+
+/// On-disk shape of `dropzone.toml`. Every field is optional since the file
+/// itself is optional and env vars / CLI args can fill in the rest.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct FileConfig {
+    port: Option<u16>,
+    upload_dir: Option<PathBuf>,
+    flat: Option<bool>,
+    no_tls: Option<bool>,
+    cert_path: Option<PathBuf>,
+    cert_key_path: Option<PathBuf>,
+    max_body_size: Option<usize>,
+    auth_tokens: Option<Vec<String>>,
+    cors_origins: Option<Vec<String>>,
+}
+
+/// The effective, fully-resolved configuration for this run, merged with
+/// precedence CLI args > env vars > `dropzone.toml` > built-in defaults.
+///
+/// `--flat`/`--no-tls` are bare flags, so they can only assert "on" from the
+/// CLI tier; to turn one back off when `dropzone.toml` enables it, set the
+/// matching `DROPZONE_FLAT`/`DROPZONE_NO_TLS` env var to `false` instead.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub port: u16,
+    pub upload_dir: PathBuf,
+    pub no_tls: bool,
+    pub cert_path: PathBuf,
+    pub cert_key_path: PathBuf,
+    pub max_body_size: Option<usize>,
+    pub auth_tokens: Option<Vec<String>>,
+    pub cors_origins: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Resolves the config for this run. `curr_dir` anchors the defaults
+    /// (`dropzone.toml`, `dropzone-uploads/`, `cert.crt`/`cert.key`).
+    pub fn load(curr_dir: &PathBuf) -> Self {
+        let args: Vec<String> = env::args().collect();
+
+        let config_path = arg_value(&args, "--config")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| curr_dir.join("dropzone.toml"));
+        let file = fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|raw| toml::from_str::<FileConfig>(&raw).ok())
+            .unwrap_or_default();
+
+        let flat = arg_flag(&args, "--flat")
+            .or_else(|| env_bool("DROPZONE_FLAT"))
+            .or(file.flat)
+            .unwrap_or(false);
+        let upload_dir = if flat {
+            curr_dir.clone()
+        } else {
+            file.upload_dir
+                .unwrap_or_else(|| curr_dir.join("dropzone-uploads"))
+        };
+
+        let port = args
+            .get(1)
+            .filter(|a| !a.starts_with("--"))
+            .and_then(|a| a.parse().ok())
+            .or_else(|| env::var("DROPZONE_PORT").ok().and_then(|v| v.parse().ok()))
+            .or(file.port)
+            .unwrap_or(8080);
+
+        let no_tls = arg_flag(&args, "--no-tls")
+            .or_else(|| env_bool("DROPZONE_NO_TLS"))
+            .or(file.no_tls)
+            .unwrap_or(false);
+
+        let cert_path = env::var("DROPZONE_CERT_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or(file.cert_path)
+            .unwrap_or_else(|| curr_dir.join("cert.crt"));
+        let cert_key_path = env::var("DROPZONE_CERT_KEY_PATH")
+            .ok()
+            .map(PathBuf::from)
+            .or(file.cert_key_path)
+            .unwrap_or_else(|| curr_dir.join("cert.key"));
+
+        let max_body_size = env::var("DROPZONE_MAX_BODY_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .or(file.max_body_size);
+
+        let auth_tokens = env::var("DROPZONE_AUTH_TOKENS")
+            .ok()
+            .map(|raw| split_list(&raw))
+            .or_else(|| {
+                env::var("DROPZONE_AUTH_TOKENS_FILE")
+                    .ok()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                    .map(|raw| split_list(&raw.replace('\n', ",")))
+            })
+            .or(file.auth_tokens);
+
+        Self {
+            port,
+            upload_dir,
+            no_tls,
+            cert_path,
+            cert_key_path,
+            max_body_size,
+            auth_tokens,
+            cors_origins: file.cors_origins,
+        }
+    }
+}
+
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// `Some(true)` when `flag` is present on the command line, `None` otherwise —
+/// a bare CLI flag can only assert "on", so an explicit "off" has to come from
+/// a lower-precedence tier (env var or TOML) instead.
+fn arg_flag(args: &[String], flag: &str) -> Option<bool> {
+    args.iter().any(|a| a == flag).then_some(true)
+}
+
+fn env_bool(key: &str) -> Option<bool> {
+    match env::var(key).ok()?.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn split_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
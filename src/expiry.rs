@@ -0,0 +1,109 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use colored::Colorize;
+use tokio::fs;
+
+use crate::AppState;
+
+/// Expiry metadata for a single stored file, keyed by its stored (digest) name.
+#[derive(Clone, Copy, Debug)]
+pub struct Expiry {
+    pub oneshot: bool,
+    pub expires_at: Option<Instant>,
+}
+
+pub type ExpiryIndex = Arc<Mutex<HashMap<String, Expiry>>>;
+
+pub fn new_index() -> ExpiryIndex {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Parses durations like `30s`, `5m`, `1h`, `2d` (no suffix defaults to seconds).
+pub fn parse_duration(raw: &str) -> Option<Duration> {
+    let raw = raw.trim();
+    let split_at = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let (num, unit) = raw.split_at(split_at);
+    let num: u64 = num.parse().ok()?;
+    let secs = match unit {
+        "" | "s" => num,
+        "m" => num * 60,
+        "h" => num * 3600,
+        "d" => num * 86400,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
+
+/// Periodically reaps files whose TTL has passed. Run as a background task.
+pub async fn sweep_task(state: AppState) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        ticker.tick().await;
+        let now = Instant::now();
+        let expired: Vec<String> = {
+            let Ok(map) = state.expiry.lock() else { continue };
+            map.iter()
+                .filter_map(|(name, meta)| match meta.expires_at {
+                    Some(at) if at <= now => Some(name.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for name in expired {
+            remove_stored(&state, &name).await;
+        }
+    }
+}
+
+/// Deletes a stored file and its metadata, logging an `EXPIRE` line.
+pub async fn remove_stored(state: &AppState, name: &str) {
+    let path = state.upload_dir.join(name);
+    if fs::remove_file(&path).await.is_ok() {
+        let ts = Local::now().format("%H:%M:%S");
+        println!(
+            "{} {} {}",
+            format!("[{ts}]").dimmed(),
+            "EXPIRE".red().bold(),
+            name
+        );
+    }
+
+    if let Ok(mut expiry) = state.expiry.lock() {
+        expiry.remove(name);
+    }
+    if let Ok(mut names) = state.names.lock() {
+        names.remove(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_defaults_to_seconds() {
+        assert_eq!(parse_duration("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("45s"), Some(Duration::from_secs(45)));
+    }
+
+    #[test]
+    fn parse_duration_supports_minutes_hours_days() {
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(5 * 60)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(2 * 86400)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_garbage() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("-5m"), None);
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use axum::{
+    Json,
+    body::Body,
+    extract::{Path as AxumPath, State},
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+use serde::Serialize;
+use tokio::fs;
+use tokio_util::io::ReaderStream;
+
+use crate::{AppState, expiry};
+
+/// Characters that can't appear unescaped in a `Content-Disposition` filename
+/// (quotes, backslash, control chars) or that would be ambiguous in the
+/// RFC 5987 `filename*` form.
+const FILENAME_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'%')
+    .add(b'\'')
+    .add(b'*')
+    .add(b'/')
+    .add(b'\\');
+
+/// Builds a `Content-Disposition: attachment` header value safe for any
+/// original filename, including one containing quotes or control characters:
+/// an ASCII-sanitized `filename` plus an RFC 5987 `filename*` for clients
+/// that support non-ASCII names.
+fn content_disposition(name: &str) -> String {
+    let ascii_fallback: String = name
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' { c } else { '_' })
+        .collect();
+    let encoded = utf8_percent_encode(name, FILENAME_ENCODE_SET);
+    let value = format!("attachment; filename=\"{ascii_fallback}\"; filename*=UTF-8''{encoded}");
+
+    if HeaderValue::from_str(&value).is_ok() {
+        value
+    } else {
+        "attachment".to_string()
+    }
+}
+
+#[derive(Serialize)]
+pub struct FileEntry {
+    name: String,
+    stored_as: String,
+    size: u64,
+    modified: Option<String>,
+}
+
+/// A `name` that would resolve outside of `root` (e.g. a `../..` escape, or a
+/// symlink pointing elsewhere) — as opposed to one that's simply missing.
+#[derive(Debug, PartialEq, Eq)]
+struct PathEscapesRoot;
+
+/// Lexically collapses `.`/`..` components without touching the filesystem,
+/// so a path can be validated before it's known to exist.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                result.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// Joins `name` onto `root`, rejecting anything that normalizes outside of it
+/// (e.g. `../..` escapes). Unlike a plain `canonicalize`-and-check, this
+/// doesn't require `name` to exist — callers can tell "escapes root" apart
+/// from "doesn't exist" by trying to open the returned path afterward.
+fn safe_join(root: &Path, name: &str) -> Result<PathBuf, PathEscapesRoot> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let candidate = normalize(&root.join(name));
+    if !candidate.starts_with(&root) {
+        return Err(PathEscapesRoot);
+    }
+
+    // Existing paths get a second, filesystem-aware check so a symlink can't
+    // point the final read outside of `root` despite passing lexically.
+    match candidate.canonicalize() {
+        Ok(resolved) if !resolved.starts_with(&root) => Err(PathEscapesRoot),
+        _ => Ok(candidate),
+    }
+}
+
+/// `GET /files` — lists the files currently sitting in the upload directory,
+/// showing each one's original name where the digest index has it recorded.
+pub async fn list_files(State(state): State<AppState>) -> impl IntoResponse {
+    let mut read_dir = match fs::read_dir(&state.upload_dir).await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to read upload dir: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(Vec::<FileEntry>::new()))
+                .into_response();
+        }
+    };
+
+    let mut entries = Vec::new();
+    while let Ok(Some(entry)) = read_dir.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        let stored_as = entry.file_name().to_string_lossy().into_owned();
+        if !metadata.is_file() || stored_as.starts_with('.') {
+            continue;
+        }
+
+        let modified = metadata
+            .modified()
+            .ok()
+            .map(|m| chrono::DateTime::<chrono::Local>::from(m).to_rfc3339());
+        let name = state
+            .names
+            .lock()
+            .ok()
+            .and_then(|names| names.get(&stored_as).cloned())
+            .unwrap_or_else(|| stored_as.clone());
+
+        entries.push(FileEntry {
+            name,
+            stored_as,
+            size: metadata.len(),
+            modified,
+        });
+    }
+
+    Json(entries).into_response()
+}
+
+/// `GET /files/{name}` — streams a previously dropped file back as an attachment,
+/// presenting it under its original upload name when the index has one.
+pub async fn download_file(
+    State(state): State<AppState>,
+    AxumPath(name): AxumPath<String>,
+) -> impl IntoResponse {
+    let path = match safe_join(&state.upload_dir, &name) {
+        Ok(path) => path,
+        Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+    };
+
+    let meta = state.expiry.lock().ok().and_then(|m| m.get(&name).copied());
+    if meta.is_some_and(|m| m.expires_at.is_some_and(|at| at <= Instant::now())) {
+        expiry::remove_stored(&state, &name).await;
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let file = match fs::File::open(&path).await {
+        Ok(f) => f,
+        Err(_) => return StatusCode::NOT_FOUND.into_response(),
+    };
+
+    let download_name = state
+        .names
+        .lock()
+        .ok()
+        .and_then(|names| names.get(&name).cloned())
+        .unwrap_or_else(|| name.clone());
+    let content_type = mime_guess::from_path(&path)
+        .first_or_octet_stream()
+        .to_string();
+    let body = Body::from_stream(ReaderStream::new(file));
+
+    if meta.is_some_and(|m| m.oneshot) {
+        expiry::remove_stored(&state, &name).await;
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CONTENT_DISPOSITION, content_disposition(&download_name)),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "dropzone-files-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn safe_join_allows_file_inside_root() {
+        let root = scratch_dir("allow");
+        std::fs::write(root.join("a.txt"), b"hi").unwrap();
+
+        let joined = safe_join(&root, "a.txt").expect("file inside root should resolve");
+        assert_eq!(joined, root.canonicalize().unwrap().join("a.txt"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn safe_join_rejects_parent_traversal() {
+        let base = scratch_dir("traverse");
+        let root = base.join("uploads");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(base.join("secret.txt"), b"do not serve me").unwrap();
+
+        assert_eq!(safe_join(&root, "../secret.txt"), Err(PathEscapesRoot));
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn safe_join_allows_nonexistent_name_inside_root() {
+        // A missing file is a 404, not a 400 — safe_join shouldn't require the
+        // name to exist to tell it apart from an escape.
+        let root = scratch_dir("missing");
+        let joined = safe_join(&root, "nope.txt").expect("name inside root should resolve");
+        assert_eq!(joined, root.canonicalize().unwrap().join("nope.txt"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -3,21 +3,48 @@ use std::{env, net::SocketAddr, path::PathBuf};
 use axum::{
     Router,
     extract::{DefaultBodyLimit, Multipart},
-    http::{StatusCode, header},
+    http::{HeaderMap, StatusCode, header},
+    middleware,
     response::{Html, IntoResponse},
     routing::{get, post},
 };
-use axum_server::tls_rustls::RustlsConfig;
 use chrono::Local;
 use colored::Colorize;
+use sha2::{Digest, Sha256};
 use tap::Pipe;
-use tokio::{fs, net::TcpListener};
+use tokio::{
+    fs,
+    io::{AsyncWriteExt, BufWriter},
+    net::TcpListener,
+};
 use tower_http::{
     cors::{Any, CorsLayer},
     limit::RequestBodyLimitLayer,
 };
 use uuid::Uuid;
 
+mod auth;
+mod config;
+mod expiry;
+mod files;
+mod storage;
+mod tls;
+
+use auth::{TokenAuth, require_auth};
+use config::Config;
+use expiry::ExpiryIndex;
+use storage::NameIndex;
+
+/// Shared app state: where uploads land, the digest -> original-name index that
+/// lets the download endpoint present content-addressed files by their
+/// human-friendly name, and the expiry metadata for one-shot/TTL drops.
+#[derive(Clone)]
+pub struct AppState {
+    pub upload_dir: PathBuf,
+    pub names: NameIndex,
+    pub expiry: ExpiryIndex,
+}
+
 const INDEX_HTML: &'static str = include_str!("index.html");
 const FAVICON_SVG: &'static str = include_str!("favicon.svg");
 
@@ -30,50 +57,123 @@ async fn favicon() -> impl IntoResponse {
 }
 
 async fn handle_upload(
-    axum::extract::State(upload_dir): axum::extract::State<PathBuf>,
+    axum::extract::State(state): axum::extract::State<AppState>,
+    headers: HeaderMap,
     mut multipart: Multipart,
 ) -> impl IntoResponse {
-    while let Ok(Some(field)) = multipart.next_field().await {
+    let ttl = headers
+        .get("X-Expire")
+        .and_then(|v| v.to_str().ok())
+        .and_then(expiry::parse_duration);
+    let mut oneshot = false;
+    // Stored names of files from this request, so the oneshot/TTL metadata
+    // below can be applied once the whole multipart body has been read —
+    // the `oneshot` field isn't guaranteed to arrive before the file field.
+    let mut uploaded = Vec::new();
+
+    while let Ok(Some(mut field)) = multipart.next_field().await {
+        if field.name() == Some("oneshot") {
+            if let Ok(text) = field.text().await {
+                oneshot = matches!(text.trim(), "true" | "1");
+            }
+            continue;
+        }
+
         let file_name = match field.file_name() {
             Some(n) if !n.is_empty() => n.to_string(),
             _ => continue,
         };
 
-        let data = match field.bytes().await {
-            Ok(d) => d,
-            Err(e) => {
-                eprintln!("Error reading field: {e}");
-                return StatusCode::INTERNAL_SERVER_ERROR;
-            }
-        };
-
-        let stem = PathBuf::from(&file_name);
-        let stem = stem.file_stem().unwrap_or_default().to_string_lossy();
         let ext = PathBuf::from(&file_name)
             .extension()
             .map(|e| format!(".{}", e.to_string_lossy()))
             .unwrap_or_default();
 
-        let unique = format!("{}-{}{}", stem, &Uuid::new_v4().to_string()[..8], ext);
-        let dest = upload_dir.join(&unique);
-
-        match fs::write(&dest, &data).await {
-            Ok(_) => {
-                let ts = Local::now().format("%H:%M:%S");
-                println!(
-                    "{} {} {} ({} bytes)",
-                    format!("[{ts}]").dimmed(),
-                    "FILE".green().bold(),
-                    unique,
-                    data.len()
-                );
-            }
+        // Stream into a temp file while hashing; the final, content-addressed name
+        // is only known once the upload finishes.
+        let tmp_name = format!(".{}.part", Uuid::new_v4());
+        let tmp_dest = state.upload_dir.join(&tmp_name);
+
+        let file = match fs::File::create(&tmp_dest).await {
+            Ok(f) => f,
             Err(e) => {
-                eprintln!("Failed to write file {unique}: {e}");
+                eprintln!("Failed to create file {tmp_name}: {e}");
                 return StatusCode::INTERNAL_SERVER_ERROR;
             }
+        };
+        let mut writer = BufWriter::new(file);
+        let mut hasher = Sha256::new();
+        let mut written: usize = 0;
+
+        let stream_result = loop {
+            match field.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Err(e) = writer.write_all(&chunk).await {
+                        break Err(e.to_string());
+                    }
+                    hasher.update(&chunk);
+                    written += chunk.len();
+                }
+                Ok(None) => break writer.flush().await.map_err(|e| e.to_string()),
+                Err(e) => break Err(e.to_string()),
+            }
+        };
+
+        if let Err(e) = stream_result {
+            eprintln!("Failed to write file {tmp_name}: {e}");
+            drop(writer);
+            let _ = fs::remove_file(&tmp_dest).await;
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        }
+        drop(writer);
+
+        let digest_name = storage::digest_file_name(hasher, &ext);
+        let dest = state.upload_dir.join(&digest_name);
+        let ts = Local::now().format("%H:%M:%S");
+
+        if fs::try_exists(&dest).await.unwrap_or(false) {
+            let _ = fs::remove_file(&tmp_dest).await;
+            println!(
+                "{} {} {} already stored as {digest_name}",
+                format!("[{ts}]").dimmed(),
+                "DEDUP".blue().bold(),
+                file_name,
+            );
+        } else if let Err(e) = fs::rename(&tmp_dest, &dest).await {
+            eprintln!("Failed to store file {digest_name}: {e}");
+            let _ = fs::remove_file(&tmp_dest).await;
+            return StatusCode::INTERNAL_SERVER_ERROR;
+        } else {
+            println!(
+                "{} {} {} ({} bytes)",
+                format!("[{ts}]").dimmed(),
+                "FILE".green().bold(),
+                digest_name,
+                written
+            );
+        }
+
+        if let Ok(mut names) = state.names.lock() {
+            names.insert(digest_name.clone(), file_name);
+        }
+
+        uploaded.push(digest_name);
+    }
+
+    if oneshot || ttl.is_some() {
+        if let Ok(mut expiry_index) = state.expiry.lock() {
+            for digest_name in uploaded {
+                expiry_index.insert(
+                    digest_name,
+                    expiry::Expiry {
+                        oneshot,
+                        expires_at: ttl.map(|d| std::time::Instant::now() + d),
+                    },
+                );
+            }
         }
     }
+
     StatusCode::OK
 }
 
@@ -97,7 +197,7 @@ async fn handle_message(mut multipart: Multipart) -> impl IntoResponse {
     StatusCode::OK
 }
 
-fn print_entry(no_tls: bool, port: u16, upload_dir: &PathBuf) {
+fn print_entry(no_tls: bool, self_signed: bool, port: u16, upload_dir: &PathBuf) {
     let local_ip = local_ip_address::local_ip()
         .map(|ip| ip.to_string())
         .unwrap_or_else(|_| "<your-ip>".to_string());
@@ -113,6 +213,14 @@ fn print_entry(no_tls: bool, port: u16, upload_dir: &PathBuf) {
     if no_tls {
         println!("{}", "Running in insecure mode".yellow().bold());
     }
+    if self_signed {
+        println!(
+            "{}",
+            "Using a generated self-signed cert — accept the browser warning on LAN clients"
+                .yellow()
+                .bold()
+        );
+    }
 
     println!("  {} {}", "Local:".bold(), local_address);
     println!("  {} {}", "Network:".bold(), network_address);
@@ -124,57 +232,71 @@ fn print_entry(no_tls: bool, port: u16, upload_dir: &PathBuf) {
 #[tokio::main]
 async fn main() {
     let curr_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let upload_dir = if env::args().any(|a| a == "--flat") {
-        curr_dir.clone()
-    } else {
-        curr_dir.join("dropzone-uploads")
+    let config = Config::load(&curr_dir);
+
+    std::fs::create_dir_all(&config.upload_dir).expect("Cannot create dropzone directory");
+
+    let state = AppState {
+        upload_dir: config.upload_dir.clone(),
+        names: storage::new_index(),
+        expiry: expiry::new_index(),
     };
 
-    std::fs::create_dir_all(&upload_dir).expect("Cannot create dropzone directory");
+    tokio::spawn(expiry::sweep_task(state.clone()));
 
-    let cors = CorsLayer::new().allow_origin(Any);
-    let max_size = env::var("DROPZONE_MAX_BODY_SIZE")
-        .ok()
-        .and_then(|a| a.parse().ok());
+    let cors = match &config.cors_origins {
+        Some(origins) if !origins.is_empty() => {
+            let parsed = origins
+                .iter()
+                .filter_map(|o| o.parse::<header::HeaderValue>().ok())
+                .collect::<Vec<_>>();
+            CorsLayer::new().allow_origin(parsed)
+        }
+        _ => CorsLayer::new().allow_origin(Any),
+    };
+
+    // Everything except the index/favicon touches uploaded content, so it all sits
+    // behind the same token gate: write endpoints and the read endpoints that can
+    // list/download what was dropped.
+    let protected_routes = Router::new()
+        .route("/upload", post(handle_upload))
+        .route("/message", post(handle_message))
+        .route("/files", get(files::list_files))
+        .route("/files/{name}", get(files::download_file))
+        .pipe(
+            |router| match TokenAuth::from_tokens(config.auth_tokens.clone().unwrap_or_default()) {
+                Some(token_auth) => {
+                    router.layer(middleware::from_fn_with_state(token_auth, require_auth::<TokenAuth>))
+                }
+                None => router,
+            },
+        );
 
     let app = Router::new()
         .route("/", get(index))
         .route("/favicon.svg", get(favicon))
-        .route("/upload", post(handle_upload))
-        .route("/message", post(handle_message))
+        .merge(protected_routes)
         .layer(DefaultBodyLimit::disable())
-        .pipe(|router| match max_size {
+        .pipe(|router| match config.max_body_size {
             Some(size) => router.layer(RequestBodyLimitLayer::new(size)),
             None => router,
         })
         .layer(cors)
-        .with_state(upload_dir.clone());
-
-    let port: u16 = env::args()
-        .nth(1)
-        .and_then(|a| a.parse().ok())
-        .unwrap_or(8080);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+        .with_state(state);
 
-    let no_tls = env::args().any(|a| a == "--no-tls");
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
 
-    print_entry(no_tls, port, &upload_dir);
-
-    if no_tls {
+    if config.no_tls {
+        print_entry(config.no_tls, false, config.port, &config.upload_dir);
         let listener = TcpListener::bind(addr).await.expect("Failed to bind port");
         axum::serve(listener, app).await.unwrap();
     } else {
-        let cert_path = env::var("DROPZONE_CERT_PATH")
-            .unwrap_or_else(|_| curr_dir.join("cert.crt").to_string_lossy().into_owned());
-        let cert_key_path = env::var("DROPZONE_CERT_KEY_PATH")
-            .unwrap_or_else(|_| curr_dir.join("cert.key").to_string_lossy().into_owned());
+        let (tls_config, self_signed) =
+            tls::load_or_generate(&config.cert_path, &config.cert_key_path).await;
 
-        let config = RustlsConfig::from_pem_file(cert_path, cert_key_path)
-            .await
-            .expect("Failed to read cert data");
+        print_entry(config.no_tls, self_signed, config.port, &config.upload_dir);
 
-        axum_server::bind_rustls(addr, config)
+        axum_server::bind_rustls(addr, tls_config)
             .serve(app.into_make_service())
             .await
             .unwrap();
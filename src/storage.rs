@@ -0,0 +1,21 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use sha2::{Digest, Sha256};
+
+/// Maps a digest-based stored file name back to the original name it was uploaded
+/// as, so the download endpoint can present something human-friendly.
+pub type NameIndex = Arc<Mutex<HashMap<String, String>>>;
+
+pub fn new_index() -> NameIndex {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+/// Turns a finished SHA-256 hash into the stored file name: the hex digest plus
+/// the original extension, so identical content uploaded with the same extension
+/// always resolves to the same name.
+pub fn digest_file_name(hasher: Sha256, ext: &str) -> String {
+    format!("{:x}{ext}", hasher.finalize())
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use axum_server::tls_rustls::RustlsConfig;
+
+/// Loads the TLS cert/key at the configured paths, or — when they don't exist,
+/// the common first-run case — generates a self-signed certificate in memory
+/// covering `localhost` and the detected LAN IP, and writes it out for next time.
+///
+/// Returns whether a certificate was generated, so the caller can warn that
+/// LAN clients will need to accept a browser security warning.
+pub async fn load_or_generate(cert_path: &Path, key_path: &Path) -> (RustlsConfig, bool) {
+    if cert_path.exists() && key_path.exists() {
+        let config = RustlsConfig::from_pem_file(cert_path, key_path)
+            .await
+            .expect("Failed to read cert data");
+        return (config, false);
+    }
+
+    let local_ip = local_ip_address::local_ip()
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|_| "127.0.0.1".to_string());
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string(), local_ip])
+        .expect("Failed to generate self-signed certificate");
+    let cert_pem = cert.cert.pem();
+    let key_pem = cert.key_pair.serialize_pem();
+
+    if let Err(e) = std::fs::write(cert_path, &cert_pem) {
+        eprintln!(
+            "Failed to write generated cert to {}: {e}",
+            cert_path.display()
+        );
+    }
+    if let Err(e) = std::fs::write(key_path, &key_pem) {
+        eprintln!(
+            "Failed to write generated cert key to {}: {e}",
+            key_path.display()
+        );
+    }
+
+    let config = RustlsConfig::from_pem(cert_pem.into_bytes(), key_pem.into_bytes())
+        .await
+        .expect("Failed to load generated self-signed certificate");
+
+    (config, true)
+}